@@ -1,9 +1,74 @@
-use crossterm::style::Color;
+use bitflags::bitflags;
+use crossterm::style::{Attribute, Color};
+
+bitflags! {
+    /// Per-cell text attributes, mirroring a terminal's SGR flags.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Attributes: u8 {
+        const BOLD = 0b0000_0001;
+        const DIM = 0b0000_0010;
+        const ITALIC = 0b0000_0100;
+        const UNDERLINE = 0b0000_1000;
+        const BLINK = 0b0001_0000;
+        const REVERSE = 0b0010_0000;
+        const HIDDEN = 0b0100_0000;
+        const STRIKEOUT = 0b1000_0000;
+    }
+}
+
+impl Attributes {
+    /// Returns the `crossterm` `SetAttribute` commands that turn on exactly the
+    /// flags contained in `self`, in a stable order.
+    pub fn to_crossterm_commands(&self) -> Vec<Attribute> {
+        let mut commands = Vec::new();
+        if self.contains(Attributes::BOLD) {
+            commands.push(Attribute::Bold);
+        }
+        if self.contains(Attributes::DIM) {
+            commands.push(Attribute::Dim);
+        }
+        if self.contains(Attributes::ITALIC) {
+            commands.push(Attribute::Italic);
+        }
+        if self.contains(Attributes::UNDERLINE) {
+            commands.push(Attribute::Underlined);
+        }
+        if self.contains(Attributes::BLINK) {
+            commands.push(Attribute::SlowBlink);
+        }
+        if self.contains(Attributes::REVERSE) {
+            commands.push(Attribute::Reverse);
+        }
+        if self.contains(Attributes::HIDDEN) {
+            commands.push(Attribute::Hidden);
+        }
+        if self.contains(Attributes::STRIKEOUT) {
+            commands.push(Attribute::CrossedOut);
+        }
+        commands
+    }
+}
+
+bitflags! {
+    /// Internal rendering flags that are not part of a cell's SGR style.
+    ///
+    /// `WIDE_CHAR` marks the leading cell of a double-width glyph, `WIDE_CHAR_SPACER`
+    /// the placeholder cell that follows it; the spacer carries an empty char and
+    /// must never be emitted on its own, because the glyph already advances the
+    /// terminal cursor by two columns.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct CellFlags: u8 {
+        const WIDE_CHAR = 0b0000_0001;
+        const WIDE_CHAR_SPACER = 0b0000_0010;
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Cell {
     pub fg_color: Color,
     pub bg_color: Color,
+    pub attributes: Attributes,
+    pub flags: CellFlags,
     pub c: char,
 }
 
@@ -12,13 +77,17 @@ impl Cell {
     pub const EMPTY: Cell = Cell {
         fg_color: Color::Reset,
         bg_color: Color::Reset,
+        attributes: Attributes::empty(),
+        flags: CellFlags::empty(),
         c: Cell::EMPTY_CHAR,
     };
 
-    pub fn new(fg_color: Color, bg_color: Color, c: char) -> Self {
+    pub fn new(fg_color: Color, bg_color: Color, attributes: Attributes, c: char) -> Self {
         Self {
             fg_color,
             bg_color,
+            attributes,
+            flags: CellFlags::empty(),
             c,
         }
     }
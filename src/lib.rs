@@ -3,19 +3,23 @@ use std::io::{self, stdout, Write};
 use cell::Cell;
 use crossterm::{
     cursor::MoveTo,
-    style::{Color, Colors, Print, SetColors},
+    style::{Attribute, Color, Colors, SetAttribute, SetColors},
     terminal::{
         self, disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
     },
-    ExecutableCommand, QueueableCommand,
+    Command, ExecutableCommand, QueueableCommand,
 };
 
+pub use cell::{Attributes, CellFlags};
+
+use unicode_width::UnicodeWidthChar;
+
 mod cell;
 mod cursor;
 mod util;
 
 pub use cursor::CursorStyle;
-use util::Point;
+pub use util::Point;
 pub use util::WindowBounds;
 
 #[derive(Debug, Clone, Copy)]
@@ -37,11 +41,13 @@ pub struct Screen {
     force_redraw: bool,
     fg_color: Color,
     bg_color: Color,
+    attributes: Attributes,
     new_cursor_style: CursorStyle,
     cur_cursor_style: CursorStyle,
     new_cursor_visibility: bool,
     cur_cursor_visibility: bool,
     windows: Vec<WindowBounds>,
+    output_buffer: Vec<u8>,
 }
 
 impl Screen {
@@ -60,6 +66,7 @@ impl Screen {
 
         let fg_color = Color::Reset;
         let bg_color = Color::Reset;
+        let attributes = Attributes::empty();
 
         let new_cursor_style = CursorStyle::DefaultUserShape;
         let cur_cursor_style = CursorStyle::DefaultUserShape;
@@ -79,14 +86,39 @@ impl Screen {
             force_redraw: false,
             fg_color,
             bg_color,
+            attributes,
             new_cursor_style,
             cur_cursor_style,
             new_cursor_visibility,
             cur_cursor_visibility,
             windows,
+            output_buffer: Vec::new(),
         })
     }
 
+    #[cfg(test)]
+    fn with_size(width: u16, height: u16) -> Self {
+        let buffer_size = width as usize * height as usize;
+        Self {
+            new_screen: vec![Screen::EMPTY_CELL; buffer_size].into_boxed_slice(),
+            cur_screen: vec![Screen::EMPTY_CELL; buffer_size].into_boxed_slice(),
+            width,
+            height,
+            cursor: Point::ZERO,
+            stored_cursor: Point::ZERO,
+            force_redraw: false,
+            fg_color: Color::Reset,
+            bg_color: Color::Reset,
+            attributes: Attributes::empty(),
+            new_cursor_style: CursorStyle::DefaultUserShape,
+            cur_cursor_style: CursorStyle::DefaultUserShape,
+            new_cursor_visibility: true,
+            cur_cursor_visibility: true,
+            windows: Vec::new(),
+            output_buffer: Vec::new(),
+        }
+    }
+
     pub fn begin(&mut self) -> io::Result<()> {
         let mut stdout = stdout();
 
@@ -249,6 +281,62 @@ impl Screen {
         }
     }
 
+    /// Scrolls the contents of the current window up by `n` rows, discarding the
+    /// top `n` rows and filling the freed bottom rows with the current `bg_color`.
+    pub fn scroll_up(&mut self, n: u16) {
+        let window = self.get_current_window();
+        let n = n.min(window.height);
+
+        let mut fill = Screen::EMPTY_CELL;
+        fill.bg_color = self.bg_color;
+
+        let width = self.width as usize;
+        let window_width = window.width as usize;
+        let window_x = window.x as usize;
+        let row_start = |row: u16| (window.y + row) as usize * width + window_x;
+
+        // Rows are non-contiguous sub-slices of the backing buffer, so move them
+        // one row at a time rather than with a single `copy_within`.
+        for row in 0..(window.height - n) {
+            let src = row_start(row + n);
+            let dst = row_start(row);
+            self.new_screen.copy_within(src..src + window_width, dst);
+        }
+
+        for row in (window.height - n)..window.height {
+            let start = row_start(row);
+            self.new_screen[start..start + window_width].fill(fill);
+        }
+    }
+
+    /// Scrolls the contents of the current window down by `n` rows, discarding the
+    /// bottom `n` rows and filling the freed top rows with the current `bg_color`.
+    pub fn scroll_down(&mut self, n: u16) {
+        let window = self.get_current_window();
+        let n = n.min(window.height);
+
+        let mut fill = Screen::EMPTY_CELL;
+        fill.bg_color = self.bg_color;
+
+        let width = self.width as usize;
+        let window_width = window.width as usize;
+        let window_x = window.x as usize;
+        let row_start = |row: u16| (window.y + row) as usize * width + window_x;
+
+        // Iterate from the bottom up so we never clobber a source row before it
+        // has been copied.
+        for row in (n..window.height).rev() {
+            let src = row_start(row - n);
+            let dst = row_start(row);
+            self.new_screen.copy_within(src..src + window_width, dst);
+        }
+
+        for row in 0..n {
+            let start = row_start(row);
+            self.new_screen[start..start + window_width].fill(fill);
+        }
+    }
+
     pub fn print<S: AsRef<str>>(&mut self, message: S) {
         let window = self.get_current_window();
         let window_x = window.x as usize;
@@ -268,10 +356,52 @@ impl Screen {
                     y = window_y;
                 }
             }
+            c if UnicodeWidthChar::width(c) == Some(2) => {
+                // Never split a wide glyph across the right edge: if it would
+                // start in the last column, blank that column and wrap first.
+                if x + 1 >= window_x + window_width {
+                    let index = y * width + x;
+                    self.new_screen[index].fg_color = self.fg_color;
+                    self.new_screen[index].bg_color = self.bg_color;
+                    self.new_screen[index].attributes = self.attributes;
+                    self.new_screen[index].flags = CellFlags::empty();
+                    self.new_screen[index].c = Cell::EMPTY_CHAR;
+
+                    x = window_x;
+                    y += 1;
+                    if y >= window_y + window_height {
+                        y = window_y;
+                    }
+                }
+
+                let index = y * width + x;
+                self.new_screen[index].fg_color = self.fg_color;
+                self.new_screen[index].bg_color = self.bg_color;
+                self.new_screen[index].attributes = self.attributes;
+                self.new_screen[index].flags = CellFlags::WIDE_CHAR;
+                self.new_screen[index].c = c;
+
+                self.new_screen[index + 1].fg_color = self.fg_color;
+                self.new_screen[index + 1].bg_color = self.bg_color;
+                self.new_screen[index + 1].attributes = self.attributes;
+                self.new_screen[index + 1].flags = CellFlags::WIDE_CHAR_SPACER;
+                self.new_screen[index + 1].c = Cell::EMPTY_CHAR;
+
+                x += 2;
+                if x >= window_x + window_width {
+                    x = window_x;
+                    y += 1;
+                    if y >= window_y + window_height {
+                        y = window_y;
+                    }
+                }
+            }
             c => {
                 let index = y * width + x;
                 self.new_screen[index].fg_color = self.fg_color;
                 self.new_screen[index].bg_color = self.bg_color;
+                self.new_screen[index].attributes = self.attributes;
+                self.new_screen[index].flags = CellFlags::empty();
                 self.new_screen[index].c = c;
 
                 x += 1;
@@ -299,12 +429,56 @@ impl Screen {
                     self.cursor.y = 0;
                 }
             }
+            c if UnicodeWidthChar::width(c) == Some(2) => {
+                // Never split a wide glyph across the right edge: if it would
+                // start in the last column, blank that column and wrap first.
+                if self.cursor.x + 1 >= window.width {
+                    let index = (window.y + self.cursor.y) as usize * self.width as usize
+                        + (window.x + self.cursor.x) as usize;
+                    self.new_screen[index].fg_color = self.fg_color;
+                    self.new_screen[index].bg_color = self.bg_color;
+                    self.new_screen[index].attributes = self.attributes;
+                    self.new_screen[index].flags = CellFlags::empty();
+                    self.new_screen[index].c = Cell::EMPTY_CHAR;
+
+                    self.cursor.x = 0;
+                    self.cursor.y += 1;
+                    if self.cursor.y >= window.height {
+                        self.cursor.y = 0;
+                    }
+                }
+
+                let index = (window.y + self.cursor.y) as usize * self.width as usize
+                    + (window.x + self.cursor.x) as usize;
+                self.new_screen[index].fg_color = self.fg_color;
+                self.new_screen[index].bg_color = self.bg_color;
+                self.new_screen[index].attributes = self.attributes;
+                self.new_screen[index].flags = CellFlags::WIDE_CHAR;
+                self.new_screen[index].c = c;
+
+                self.new_screen[index + 1].fg_color = self.fg_color;
+                self.new_screen[index + 1].bg_color = self.bg_color;
+                self.new_screen[index + 1].attributes = self.attributes;
+                self.new_screen[index + 1].flags = CellFlags::WIDE_CHAR_SPACER;
+                self.new_screen[index + 1].c = Cell::EMPTY_CHAR;
+
+                self.cursor.x += 2;
+                if self.cursor.x >= window.width {
+                    self.cursor.x = 0;
+                    self.cursor.y += 1;
+                    if self.cursor.y >= window.height {
+                        self.cursor.y = 0;
+                    }
+                }
+            }
             c => {
                 // Override cell
                 let index = (window.y + self.cursor.y) as usize * self.width as usize
                     + (window.x + self.cursor.x) as usize;
                 self.new_screen[index].fg_color = self.fg_color;
                 self.new_screen[index].bg_color = self.bg_color;
+                self.new_screen[index].attributes = self.attributes;
+                self.new_screen[index].flags = CellFlags::empty();
                 self.new_screen[index].c = c;
 
                 self.cursor.x += 1;
@@ -330,6 +504,144 @@ impl Screen {
         self.cursor.y = y.clamp(0, window.height - 1);
     }
 
+    /// Returns the cell at the window-relative position, or `None` if it lies
+    /// outside the current window.
+    pub fn get_cell(&self, x: u16, y: u16) -> Option<Cell> {
+        let window = self.get_current_window();
+        if x >= window.width || y >= window.height {
+            return None;
+        }
+
+        let index =
+            (window.y + y) as usize * self.width as usize + (window.x + x) as usize;
+        Some(self.new_screen[index])
+    }
+
+    /// Returns the text of a window-relative row with trailing blank cells
+    /// trimmed. Spacer cells are skipped so a wide glyph reads as a single char.
+    pub fn row_text(&self, y: u16) -> String {
+        let window = self.get_current_window();
+        if y >= window.height {
+            return String::new();
+        }
+
+        let start = (window.y + y) as usize * self.width as usize + window.x as usize;
+        let row = &self.new_screen[start..start + window.width as usize];
+        row_text_trimmed(row)
+    }
+
+    /// Returns the text of a window-relative rectangle, one trimmed line per row
+    /// joined with `\n`.
+    pub fn region_text(&self, bounds: WindowBounds) -> String {
+        let window = self.get_current_window();
+        if bounds.x >= window.width {
+            return String::new();
+        }
+
+        let width = bounds.width.min(window.width - bounds.x) as usize;
+        let mut lines = Vec::with_capacity(bounds.height as usize);
+        for row in 0..bounds.height {
+            let y = bounds.y + row;
+            if y >= window.height {
+                break;
+            }
+
+            let start = (window.y + y) as usize * self.width as usize
+                + (window.x + bounds.x) as usize;
+            lines.push(row_text_trimmed(&self.new_screen[start..start + width]));
+        }
+
+        lines.join("\n")
+    }
+
+    /// Finds every non-overlapping occurrence of `pattern` on screen, returning
+    /// the inclusive start/end cell coordinates of each match. Matches follow
+    /// line wraps, so a match that crosses a row boundary yields a continuous
+    /// range.
+    pub fn find_all(&self, pattern: &str) -> Vec<(Point, Point)> {
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+
+        let (text, byte_points) = self.flatten_window();
+        text.match_indices(pattern)
+            .map(|(start, matched)| {
+                (byte_points[start], byte_points[start + matched.len() - 1])
+            })
+            .collect()
+    }
+
+    /// Like [`Screen::find_all`], but treats `pattern` as a regular expression.
+    #[cfg(feature = "regex")]
+    pub fn find_all_regex(
+        &self,
+        pattern: &str,
+    ) -> Result<Vec<(Point, Point)>, regex::Error> {
+        let regex = regex::Regex::new(pattern)?;
+        let (text, byte_points) = self.flatten_window();
+        Ok(regex
+            .find_iter(&text)
+            .filter(|m| !m.is_empty())
+            .map(|m| (byte_points[m.start()], byte_points[m.end() - 1]))
+            .collect())
+    }
+
+    /// Overlays `fg`/`bg` and `attributes` onto the cells in the inclusive
+    /// `range` (as returned by [`Screen::find_all`]) without disturbing their
+    /// characters, so the next [`Screen::show`] repaints only the affected
+    /// cells.
+    pub fn highlight(
+        &mut self,
+        range: (Point, Point),
+        fg: Color,
+        bg: Color,
+        attributes: Attributes,
+    ) {
+        let window = self.get_current_window();
+        let window_width = window.width as usize;
+
+        let (start, end) = range;
+        let start_index = start.y as usize * window_width + start.x as usize;
+        let end_index = end.y as usize * window_width + end.x as usize;
+
+        for flat in start_index..=end_index {
+            let x = flat % window_width;
+            let y = flat / window_width;
+            let index = (window.y as usize + y) * self.width as usize + window.x as usize + x;
+            self.new_screen[index].fg_color = fg;
+            self.new_screen[index].bg_color = bg;
+            self.new_screen[index].attributes = attributes;
+        }
+    }
+
+    /// Reconstructs the current window as a single string in reading order
+    /// (spacers skipped) alongside a per-byte map back to cell coordinates, so
+    /// match offsets can be translated into [`Point`]s.
+    fn flatten_window(&self) -> (String, Vec<Point>) {
+        let window = self.get_current_window();
+
+        let mut text = String::new();
+        let mut byte_points = Vec::new();
+        let mut buf = [0u8; 4];
+        for y in 0..window.height {
+            for x in 0..window.width {
+                let index =
+                    (window.y + y) as usize * self.width as usize + (window.x + x) as usize;
+                let cell = &self.new_screen[index];
+                if cell.flags.contains(CellFlags::WIDE_CHAR_SPACER) {
+                    continue;
+                }
+
+                let point = Point::new(x, y);
+                let encoded = cell.c.encode_utf8(&mut buf);
+                byte_points.extend(std::iter::repeat_n(point, encoded.len()));
+                text.push_str(encoded);
+            }
+        }
+
+        (text, byte_points)
+    }
+
     pub fn clear_colors(&mut self) {
         self.fg_color = Color::Reset;
         self.bg_color = Color::Reset;
@@ -348,6 +660,18 @@ impl Screen {
         self.bg_color = background_color;
     }
 
+    pub fn clear_attributes(&mut self) {
+        self.attributes = Attributes::empty();
+    }
+
+    pub fn set_attributes(&mut self, attributes: Attributes) {
+        self.attributes = attributes;
+    }
+
+    pub fn add_attribute(&mut self, attribute: Attributes) {
+        self.attributes.insert(attribute);
+    }
+
     pub fn resize(&mut self, width: u16, height: u16) {
         // FIXME: fix all windows after resize or don't allow resize with active windows
 
@@ -384,15 +708,21 @@ impl Screen {
     }
 
     pub fn print_whole_screen(&mut self) -> io::Result<()> {
-        let mut stdout = stdout();
-
         let width = self.width as usize;
         let height = self.height as usize;
 
+        self.output_buffer.clear();
+
         let mut fg_color = Color::Reset;
         let mut bg_color = Color::Reset;
+        let mut attributes = Attributes::empty();
+        let mut prev_end: Option<usize> = None;
 
-        stdout.queue(SetColors(Colors::new(fg_color, bg_color)))?;
+        // SGR attributes are sticky across writes, so normalize them to the
+        // default at frame start (alongside the colors) to avoid bleeding a
+        // previous frame's BOLD/UNDERLINE/… into this one.
+        push_command(&mut self.output_buffer, SetAttribute(Attribute::Reset));
+        push_command(&mut self.output_buffer, SetColors(Colors::new(fg_color, bg_color)));
 
         let mut x = 0;
         let mut y = 0;
@@ -401,24 +731,30 @@ impl Screen {
         while y < height {
             let new_cell = &self.new_screen[i];
 
-            if new_cell.fg_color != fg_color || new_cell.bg_color != bg_color {
-                // print remaining deltas with previous colors
-                if start < i {
-                    let x = start % width;
-                    let y = start / width;
-                    stdout.queue(MoveTo(x as u16, y as u16))?;
-                    stdout.queue(Print(
-                        self.new_screen[start..i]
-                            .iter()
-                            .map(|cell| cell.c)
-                            .collect::<String>(),
-                    ))?;
-                }
-
-                // change colors
-                fg_color = new_cell.fg_color;
-                bg_color = new_cell.bg_color;
-                stdout.queue(SetColors(Colors::new(fg_color, bg_color)))?;
+            if new_cell.fg_color != fg_color
+                || new_cell.bg_color != bg_color
+                || new_cell.attributes != attributes
+            {
+                // flush the pending run with the previous style
+                write_run(
+                    &mut self.output_buffer,
+                    &self.new_screen,
+                    start,
+                    i,
+                    width,
+                    &mut prev_end,
+                );
+
+                // change style
+                write_style_diff(
+                    &mut self.output_buffer,
+                    &mut fg_color,
+                    &mut bg_color,
+                    &mut attributes,
+                    new_cell.fg_color,
+                    new_cell.bg_color,
+                    new_cell.attributes,
+                );
 
                 start = i;
             }
@@ -430,17 +766,14 @@ impl Screen {
                 y += 1;
             }
         }
-        if start < i {
-            let x = start % width;
-            let y = start / width;
-            stdout.queue(MoveTo(x as u16, y as u16))?;
-            stdout.queue(Print(
-                self.new_screen[start..i]
-                    .iter()
-                    .map(|cell| cell.c)
-                    .collect::<String>(),
-            ))?;
-        }
+        write_run(
+            &mut self.output_buffer,
+            &self.new_screen,
+            start,
+            i,
+            width,
+            &mut prev_end,
+        );
 
         Ok(())
     }
@@ -455,10 +788,21 @@ impl Screen {
             let width = self.width as usize;
             let height = self.height as usize;
 
+            self.output_buffer.clear();
+
             let mut fg_color = Color::Reset;
             let mut bg_color = Color::Reset;
-
-            stdout.queue(SetColors(Colors::new(fg_color, bg_color)))?;
+            let mut attributes = Attributes::empty();
+            let mut prev_end: Option<usize> = None;
+
+            // SGR attributes are sticky across writes, so normalize them to
+            // the default at frame start (alongside the colors) to avoid
+            // bleeding a previous frame's BOLD/UNDERLINE/… into this one.
+            push_command(&mut self.output_buffer, SetAttribute(Attribute::Reset));
+            push_command(
+                &mut self.output_buffer,
+                SetColors(Colors::new(fg_color, bg_color)),
+            );
 
             let mut x = 0;
             let mut y = 0;
@@ -468,18 +812,24 @@ impl Screen {
                 let new_cell = &self.new_screen[i];
                 let cur_cell = &self.cur_screen[i];
 
-                if new_cell == cur_cell {
-                    if start < i {
-                        let x = start % width;
-                        let y = start / width;
-                        stdout.queue(MoveTo(x as u16, y as u16))?;
-                        stdout.queue(Print(
-                            self.new_screen[start..i]
-                                .iter()
-                                .map(|cell| cell.c)
-                                .collect::<String>(),
-                        ))?;
-                    }
+                // A spacer belongs to the wide glyph in the preceding cell; if
+                // that glyph changed, redraw the pair together so the partial
+                // repaint stays consistent.
+                let spacer_follows_dirty_glyph = new_cell
+                    .flags
+                    .contains(CellFlags::WIDE_CHAR_SPACER)
+                    && i > 0
+                    && self.new_screen[i - 1] != self.cur_screen[i - 1];
+
+                if new_cell == cur_cell && !spacer_follows_dirty_glyph {
+                    write_run(
+                        &mut self.output_buffer,
+                        &self.new_screen,
+                        start,
+                        i,
+                        width,
+                        &mut prev_end,
+                    );
 
                     i += 1;
                     start = i;
@@ -490,24 +840,30 @@ impl Screen {
                         y += 1;
                     }
                 } else {
-                    if new_cell.fg_color != fg_color || new_cell.bg_color != bg_color {
-                        // print remaining deltas with previous colors
-                        if start < i {
-                            let x = start % width;
-                            let y = start / width;
-                            stdout.queue(MoveTo(x as u16, y as u16))?;
-                            stdout.queue(Print(
-                                self.new_screen[start..i]
-                                    .iter()
-                                    .map(|cell| cell.c)
-                                    .collect::<String>(),
-                            ))?;
-                        }
-
-                        // change colors
-                        fg_color = new_cell.fg_color;
-                        bg_color = new_cell.bg_color;
-                        stdout.queue(SetColors(Colors::new(fg_color, bg_color)))?;
+                    if new_cell.fg_color != fg_color
+                        || new_cell.bg_color != bg_color
+                        || new_cell.attributes != attributes
+                    {
+                        // flush the pending run with the previous style
+                        write_run(
+                            &mut self.output_buffer,
+                            &self.new_screen,
+                            start,
+                            i,
+                            width,
+                            &mut prev_end,
+                        );
+
+                        // change style
+                        write_style_diff(
+                            &mut self.output_buffer,
+                            &mut fg_color,
+                            &mut bg_color,
+                            &mut attributes,
+                            new_cell.fg_color,
+                            new_cell.bg_color,
+                            new_cell.attributes,
+                        );
 
                         start = i;
                     }
@@ -520,33 +876,38 @@ impl Screen {
                     }
                 }
             }
-            if start < i {
-                let x = start % width;
-                let y = start / width;
-                stdout.queue(MoveTo(x as u16, y as u16))?;
-                stdout.queue(Print(
-                    self.new_screen[start..i]
-                        .iter()
-                        .map(|cell| cell.c)
-                        .collect::<String>(),
-                ))?;
-            }
+            write_run(
+                &mut self.output_buffer,
+                &self.new_screen,
+                start,
+                i,
+                width,
+                &mut prev_end,
+            );
         }
 
         if self.new_cursor_style != self.cur_cursor_style {
-            stdout.queue(self.new_cursor_style.to_crossterm_command())?;
+            push_command(
+                &mut self.output_buffer,
+                self.new_cursor_style.to_crossterm_command(),
+            );
             self.cur_cursor_style = self.new_cursor_style;
         }
 
         if self.new_cursor_visibility != self.cur_cursor_visibility {
             match self.new_cursor_visibility {
-                true => stdout.queue(crossterm::cursor::Show),
-                false => stdout.queue(crossterm::cursor::Hide),
-            }?;
+                true => push_command(&mut self.output_buffer, crossterm::cursor::Show),
+                false => push_command(&mut self.output_buffer, crossterm::cursor::Hide),
+            }
             self.cur_cursor_visibility = self.new_cursor_visibility;
         }
 
-        stdout.queue(MoveTo(self.cursor.x, self.cursor.y))?;
+        push_command(
+            &mut self.output_buffer,
+            MoveTo(self.cursor.x, self.cursor.y),
+        );
+
+        stdout.write_all(&self.output_buffer)?;
         stdout.flush()?;
 
         self.force_redraw = false;
@@ -556,3 +917,214 @@ impl Screen {
         Ok(())
     }
 }
+
+/// Collects the characters of `row` up to the last non-empty cell, skipping
+/// spacer cells so a wide glyph reads as a single char.
+fn row_text_trimmed(row: &[Cell]) -> String {
+    let len = row
+        .iter()
+        .rposition(|cell| cell.c != Cell::EMPTY_CHAR)
+        .map_or(0, |pos| pos + 1);
+
+    row[..len]
+        .iter()
+        .filter(|cell| !cell.flags.contains(CellFlags::WIDE_CHAR_SPACER))
+        .map(|cell| cell.c)
+        .collect()
+}
+
+/// Adapter that lets `crossterm` commands serialize their ANSI form straight
+/// into a byte buffer.
+struct AnsiBuffer<'a>(&'a mut Vec<u8>);
+
+impl std::fmt::Write for AnsiBuffer<'_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.0.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Appends the ANSI form of a `crossterm` command to `buffer`.
+fn push_command(buffer: &mut Vec<u8>, command: impl Command) {
+    let _ = command.write_ansi(&mut AnsiBuffer(buffer));
+}
+
+/// Writes the glyphs of `cells[start..end]` into `buffer`, positioning the
+/// cursor first unless the run continues exactly where the previous one ended
+/// on the same row. Spacer cells are skipped because the wide glyph they follow
+/// already advances the terminal cursor by two columns.
+fn write_run(
+    buffer: &mut Vec<u8>,
+    cells: &[Cell],
+    start: usize,
+    end: usize,
+    width: usize,
+    prev_end: &mut Option<usize>,
+) {
+    if start >= end {
+        return;
+    }
+
+    if *prev_end != Some(start) || start.is_multiple_of(width) {
+        push_command(buffer, MoveTo((start % width) as u16, (start / width) as u16));
+    }
+
+    let mut encoded = [0u8; 4];
+    for cell in &cells[start..end] {
+        if cell.flags.contains(CellFlags::WIDE_CHAR_SPACER) {
+            continue;
+        }
+        buffer.extend_from_slice(cell.c.encode_utf8(&mut encoded).as_bytes());
+    }
+
+    *prev_end = Some(end);
+}
+
+/// Writes the minimal SGR commands that move the terminal from the currently
+/// tracked (`cur_*`) style to the one requested by the next cell into `buffer`,
+/// updating the tracked state in place.
+///
+/// Attributes can only be cleared as a group, so whenever a flag is turned off
+/// we emit a full `Attribute::Reset` and re-apply the colors and remaining
+/// flags; when flags are only added we emit just the new ones.
+fn write_style_diff(
+    buffer: &mut Vec<u8>,
+    cur_fg: &mut Color,
+    cur_bg: &mut Color,
+    cur_attributes: &mut Attributes,
+    new_fg: Color,
+    new_bg: Color,
+    new_attributes: Attributes,
+) {
+    if *cur_attributes != new_attributes {
+        let removed = cur_attributes.difference(new_attributes);
+        if !removed.is_empty() {
+            // A reset (SGR 0) also clears the colors, so re-apply everything.
+            push_command(buffer, SetAttribute(Attribute::Reset));
+            push_command(buffer, SetColors(Colors::new(new_fg, new_bg)));
+            for attribute in new_attributes.to_crossterm_commands() {
+                push_command(buffer, SetAttribute(attribute));
+            }
+            *cur_fg = new_fg;
+            *cur_bg = new_bg;
+            *cur_attributes = new_attributes;
+            return;
+        }
+
+        for attribute in new_attributes.difference(*cur_attributes).to_crossterm_commands() {
+            push_command(buffer, SetAttribute(attribute));
+        }
+        *cur_attributes = new_attributes;
+    }
+
+    if *cur_fg != new_fg || *cur_bg != new_bg {
+        push_command(buffer, SetColors(Colors::new(new_fg, new_bg)));
+        *cur_fg = new_fg;
+        *cur_bg = new_bg;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_cell_is_window_relative_and_bounded() {
+        let mut screen = Screen::with_size(10, 3);
+        screen.print("hi");
+
+        assert_eq!(screen.get_cell(0, 0).unwrap().c, 'h');
+        assert_eq!(screen.get_cell(1, 0).unwrap().c, 'i');
+        assert_eq!(screen.get_cell(2, 0).unwrap().c, Screen::EMPTY_CHAR);
+        assert!(screen.get_cell(10, 0).is_none());
+        assert!(screen.get_cell(0, 3).is_none());
+    }
+
+    #[test]
+    fn row_text_trims_trailing_blanks() {
+        let mut screen = Screen::with_size(10, 2);
+        screen.print("hi");
+
+        assert_eq!(screen.row_text(0), "hi");
+        assert_eq!(screen.row_text(1), "");
+    }
+
+    #[test]
+    fn row_text_skips_wide_char_spacer() {
+        let mut screen = Screen::with_size(10, 1);
+        screen.print("a世b");
+
+        // The spacer that follows the wide glyph must not appear in the text.
+        assert_eq!(screen.row_text(0), "a世b");
+        assert!(screen.get_cell(1, 0).unwrap().flags.contains(CellFlags::WIDE_CHAR));
+        assert!(screen
+            .get_cell(2, 0)
+            .unwrap()
+            .flags
+            .contains(CellFlags::WIDE_CHAR_SPACER));
+    }
+
+    #[test]
+    fn region_text_joins_trimmed_lines() {
+        let mut screen = Screen::with_size(10, 3);
+        screen.print("ab\ncd");
+
+        let text = screen.region_text(WindowBounds::new(0, 0, 10, 3));
+        assert_eq!(text, "ab\ncd\n");
+    }
+
+    #[test]
+    fn find_all_returns_match_coordinates() {
+        let mut screen = Screen::with_size(10, 2);
+        screen.print("abcabc");
+
+        let matches = screen.find_all("bc");
+        assert_eq!(matches.len(), 2);
+        let (start, end) = matches[0];
+        assert_eq!((start.x, start.y), (1, 0));
+        assert_eq!((end.x, end.y), (2, 0));
+    }
+
+    #[test]
+    fn find_all_follows_line_wrap() {
+        let mut screen = Screen::with_size(3, 2);
+        // Fills row 0 with "abc" and wraps "de" onto row 1.
+        screen.print("abcde");
+
+        let matches = screen.find_all("cd");
+        assert_eq!(matches.len(), 1);
+        let (start, end) = matches[0];
+        assert_eq!((start.x, start.y), (2, 0));
+        assert_eq!((end.x, end.y), (0, 1));
+    }
+
+    #[test]
+    fn highlight_overlays_style_without_touching_chars() {
+        let mut screen = Screen::with_size(10, 1);
+        screen.print("abc");
+
+        let range = (Point::new(1, 0), Point::new(2, 0));
+        screen.highlight(range, Color::Red, Color::Blue, Attributes::BOLD);
+
+        let cell = screen.get_cell(1, 0).unwrap();
+        assert_eq!(cell.c, 'b');
+        assert_eq!(cell.fg_color, Color::Red);
+        assert_eq!(cell.bg_color, Color::Blue);
+        assert_eq!(cell.attributes, Attributes::BOLD);
+        // Neighbour outside the range is untouched.
+        assert_eq!(screen.get_cell(0, 0).unwrap().fg_color, Color::Reset);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn find_all_regex_crosses_row_boundary() {
+        let mut screen = Screen::with_size(3, 2);
+        screen.print("abcde");
+
+        let matches = screen.find_all_regex("c.e").unwrap();
+        assert_eq!(matches.len(), 1);
+        let (start, end) = matches[0];
+        assert_eq!((start.x, start.y), (2, 0));
+        assert_eq!((end.x, end.y), (1, 1));
+    }
+}